@@ -1,7 +1,9 @@
 //! Animation for the game engine Bevy
 
 mod animatable;
+mod secondary_motion;
 mod util;
+mod velocity;
 
 use std::hash::{Hash, Hasher};
 use std::iter;
@@ -24,12 +26,15 @@ use bevy_utils::{tracing::error, NoOpHash};
 use sha1_smol::Sha1;
 use uuid::Uuid;
 
+pub use secondary_motion::{simulate_secondary_motion, SecondaryMotion};
+pub use velocity::{record_animation_velocity, AnimationVelocity};
+
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        animatable::*, AnimationClip, AnimationPlayer, AnimationPlugin, Interpolation, Keyframes,
-        VariableCurve,
+        animatable::*, AnimationClip, AnimationPlayer, AnimationPlugin, AnimationSystem,
+        AnimationVelocity, Interpolation, Keyframes, SecondaryMotion, VariableCurve,
     };
 }
 
@@ -679,6 +684,20 @@ where
         + tangent_in_end * step_duration * (lerp.powi(3) - lerp.powi(2))
 }
 
+/// Labels for the systems added by [`AnimationPlugin`], all of which run in
+/// [`PostUpdate`].
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AnimationSystem {
+    /// Advances [`AnimationPlayer`]s and applies their clips to the animation
+    /// targets.
+    Animate,
+    /// Applies [`SecondaryMotion`] on top of the animated pose, before
+    /// transform propagation.
+    SecondaryMotion,
+    /// Updates [`AnimationVelocity`] after transform propagation.
+    RecordVelocity,
+}
+
 /// Adds animation support to an app
 #[derive(Default)]
 pub struct AnimationPlugin;
@@ -689,11 +708,27 @@ impl Plugin for AnimationPlugin {
             .register_asset_reflect::<AnimationClip>()
             .register_type::<AnimationPlayer>()
             .register_type::<AnimationTarget>()
-            .add_systems(
+            .register_type::<SecondaryMotion>()
+            .register_type::<AnimationVelocity>()
+            .configure_sets(
                 PostUpdate,
-                (advance_animations, animate_targets)
+                (AnimationSystem::Animate, AnimationSystem::SecondaryMotion)
                     .chain()
                     .before(TransformSystem::TransformPropagate),
+            )
+            .configure_sets(
+                PostUpdate,
+                AnimationSystem::RecordVelocity.after(TransformSystem::TransformPropagate),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (advance_animations, animate_targets)
+                        .chain()
+                        .in_set(AnimationSystem::Animate),
+                    simulate_secondary_motion.in_set(AnimationSystem::SecondaryMotion),
+                    record_animation_velocity.in_set(AnimationSystem::RecordVelocity),
+                ),
             );
     }
 }
@@ -0,0 +1,379 @@
+//! Cheap procedural follow-through for animation targets, such as hair, tails,
+//! and pouches.
+
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::Parent;
+use bevy_math::Vec3;
+use bevy_reflect::Reflect;
+use bevy_time::Time;
+use bevy_transform::prelude::{GlobalTransform, Transform};
+
+use crate::AnimationTarget;
+
+/// Makes the translation of an [`AnimationTarget`] (e.g. a bone) lag behind
+/// its animated pose, simulated as a damped spring.
+///
+/// The simulation runs every frame after the [`AnimationPlayer`] has evaluated
+/// its clips, and before transform propagation. The animated local translation
+/// is placed in world space using the parent's [`GlobalTransform`] from the
+/// previous frame, and a simulated point is pulled towards it. The result is
+/// written back to the [`Transform`] of the target. If the target has no
+/// [`Parent`], the simulation happens directly in the space of its
+/// [`Transform`].
+///
+/// Only the translation is simulated; rotation and scale are left as the
+/// animation wrote them. On a chain of bones, such as a tail, this stretches
+/// the segments rather than swinging them, so prefer placing this component on
+/// the ends of chains or on single bones like pouches and earrings.
+///
+/// The spring is integrated in substeps of at most [`Self::MAX_SUBSTEP`]
+/// seconds, with the damping applied implicitly. It stays stable at low frame
+/// rates for any `damping`, as long as `stiffness` is well below
+/// `4 / MAX_SUBSTEP²`.
+///
+/// While the clock is paused, the target holds its last simulated pose.
+///
+/// Entities without an [`AnimationTarget`] are ignored.
+///
+/// [`AnimationPlayer`]: crate::AnimationPlayer
+#[derive(Clone, Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct SecondaryMotion {
+    /// How strongly the simulated point is pulled towards the animated pose.
+    pub stiffness: f32,
+    /// How quickly the velocity of the simulated point decays.
+    pub damping: f32,
+    /// How much of [`Self::GRAVITY`] is applied to the simulated point. `0.0`
+    /// disables gravity.
+    pub gravity_influence: f32,
+
+    /// The simulated position in world space, or `None` if the simulation
+    /// hasn't started yet.
+    #[reflect(ignore)]
+    position: Option<Vec3>,
+    /// The simulated velocity in world space.
+    #[reflect(ignore)]
+    velocity: Vec3,
+    /// The local translation produced by the animation during the last update.
+    #[reflect(ignore)]
+    animated_translation: Vec3,
+    /// The local translation this component wrote during the last update, or
+    /// `None` if it hasn't written one yet.
+    #[reflect(ignore)]
+    simulated_translation: Option<Vec3>,
+}
+
+impl Default for SecondaryMotion {
+    fn default() -> Self {
+        Self {
+            stiffness: 100.0,
+            damping: 10.0,
+            gravity_influence: 0.0,
+            position: None,
+            velocity: Vec3::ZERO,
+            animated_translation: Vec3::ZERO,
+            simulated_translation: None,
+        }
+    }
+}
+
+impl SecondaryMotion {
+    /// The world-space gravity that [`Self::gravity_influence`] scales.
+    pub const GRAVITY: Vec3 = Vec3::new(0.0, -9.81, 0.0);
+
+    /// The longest time step, in seconds, that the spring is advanced by at
+    /// once. Longer frames are split into several substeps.
+    pub const MAX_SUBSTEP: f32 = 1.0 / 120.0;
+
+    /// Creates a new [`SecondaryMotion`] with the given spring parameters.
+    pub fn new(stiffness: f32, damping: f32, gravity_influence: f32) -> Self {
+        Self {
+            stiffness,
+            damping,
+            gravity_influence,
+            ..Default::default()
+        }
+    }
+
+    /// The simulated velocity in world space.
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Stops the spring and snaps the target back to its animated pose on the
+    /// next update, e.g. when the character respawns.
+    pub fn reset(&mut self) {
+        // `animated_translation` and `simulated_translation` are kept, so that
+        // a translation the clip doesn't animate is still recognized as our
+        // own output rather than being mistaken for the animated pose.
+        self.position = None;
+        self.velocity = Vec3::ZERO;
+    }
+
+    /// Advances the simulated point towards `target` by `delta` seconds and
+    /// returns its new position.
+    fn step(&mut self, target: Vec3, delta: f32) -> Vec3 {
+        let mut position = self.position.unwrap_or(target);
+
+        let substeps = (delta / Self::MAX_SUBSTEP).ceil().max(1.0);
+        let substep = delta / substeps;
+        for _ in 0..substeps as u32 {
+            let acceleration =
+                (target - position) * self.stiffness + Self::GRAVITY * self.gravity_influence;
+            // Semi-implicit Euler: update the velocity first so that stiff
+            // springs stay stable. The damping is solved implicitly, so that
+            // it can't overshoot and flip the velocity.
+            self.velocity =
+                (self.velocity + acceleration * substep) / (1.0 + self.damping * substep);
+            position += self.velocity * substep;
+        }
+
+        self.position = Some(position);
+        position
+    }
+}
+
+/// A system that applies [`SecondaryMotion`] to animation targets on top of
+/// the pose written by [`animate_targets`](crate::animate_targets).
+pub fn simulate_secondary_motion(
+    time: Res<Time>,
+    mut targets: Query<
+        (&mut SecondaryMotion, &mut Transform, Option<&Parent>),
+        With<AnimationTarget>,
+    >,
+    parents: Query<&GlobalTransform>,
+) {
+    let delta = time.delta_seconds();
+
+    targets
+        .par_iter_mut()
+        .for_each(|(mut motion, mut transform, parent)| {
+            // If the clip doesn't animate the translation, the transform still
+            // holds our output from the last frame. Use the last animated
+            // translation instead so that the spring doesn't chase itself.
+            let animated_translation =
+                if motion.simulated_translation == Some(transform.translation) {
+                    motion.animated_translation
+                } else {
+                    transform.translation
+                };
+
+            let parent_transform = parent
+                .and_then(|parent| parents.get(parent.get()).ok())
+                .copied()
+                .unwrap_or_default();
+            let parent_affine = parent_transform.affine();
+            // A parent scaled to zero, e.g. to hide an attachment, can't be
+            // inverted, so there is no local translation to write back. The
+            // parent may reappear anywhere, so restart from the animated pose.
+            if parent_affine.matrix3.determinant() == 0.0 {
+                motion.reset();
+                return;
+            }
+
+            let target = parent_affine.transform_point3(animated_translation);
+            let position = if delta > 0.0 {
+                motion.step(target, delta)
+            } else {
+                // The clip is still applied while the clock is paused, so keep
+                // writing the simulated pose to avoid popping to the raw one.
+                motion.position.unwrap_or(target)
+            };
+            let simulated_translation = parent_affine.inverse().transform_point3(position);
+
+            motion.animated_translation = animated_translation;
+            motion.simulated_translation = Some(simulated_translation);
+            transform.translation = simulated_translation;
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_app::{App, Update};
+    use bevy_core::{Name, TaskPoolPlugin};
+    use bevy_ecs::prelude::*;
+    use bevy_hierarchy::{BuildWorldChildren, Parent};
+    use bevy_math::Vec3;
+    use bevy_time::Time;
+    use bevy_transform::prelude::{GlobalTransform, Transform};
+
+    use super::{simulate_secondary_motion, SecondaryMotion};
+    use crate::{AnimationTarget, AnimationTargetId};
+
+    const REST: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+    const FRAME: f32 = 1.0 / 60.0;
+
+    fn setup(parent_transform: Transform, motion: SecondaryMotion) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .init_resource::<Time>()
+            .add_systems(Update, simulate_secondary_motion);
+
+        let parent = app
+            .world
+            .spawn((parent_transform, GlobalTransform::from(parent_transform)))
+            .id();
+        let target = app
+            .world
+            .spawn((
+                Transform::from_translation(REST),
+                AnimationTarget {
+                    id: AnimationTargetId::from_name(&Name::new("bone")),
+                    player: parent,
+                },
+                motion,
+            ))
+            .set_parent(parent)
+            .id();
+
+        (app, target)
+    }
+
+    fn update(app: &mut App, delta: f32) {
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(delta));
+        app.update();
+    }
+
+    fn translation(app: &App, entity: Entity) -> Vec3 {
+        app.world.get::<Transform>(entity).unwrap().translation
+    }
+
+    #[test]
+    fn step_is_stable_at_low_frame_rates() {
+        for mut motion in [
+            SecondaryMotion::new(100.0, 10.0, 1.0),
+            SecondaryMotion::new(1000.0, 30.0, 1.0),
+        ] {
+            let equilibrium = SecondaryMotion::GRAVITY / motion.stiffness;
+            motion.step(Vec3::ZERO, 0.25);
+
+            let mut position = motion.step(Vec3::X, 0.25);
+            for _ in 0..50 {
+                position = motion.step(Vec3::X, 0.25);
+                assert!(position.is_finite());
+            }
+            assert!(position.distance(Vec3::X + equilibrium) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn unanimated_translation_settles_without_drifting() {
+        let parent_transform = Transform::from_xyz(3.0, 0.0, 0.0).with_scale(Vec3::splat(2.0));
+        let (mut app, target) = setup(parent_transform, SecondaryMotion::new(100.0, 10.0, 1.0));
+
+        for _ in 0..600 {
+            update(&mut app, FRAME);
+        }
+        // The sag is `GRAVITY / stiffness` in world space, halved by the
+        // parent's scale.
+        let settled = REST + SecondaryMotion::GRAVITY / 100.0 / 2.0;
+        assert!(translation(&app, target).distance(settled) < 1e-3);
+
+        for _ in 0..600 {
+            update(&mut app, FRAME);
+        }
+        assert!(translation(&app, target).distance(settled) < 1e-3);
+    }
+
+    #[test]
+    fn animated_translation_lags_behind() {
+        let (mut app, target) = setup(Transform::IDENTITY, SecondaryMotion::default());
+        update(&mut app, FRAME);
+        assert!(translation(&app, target).distance(REST) < 1e-5);
+
+        // Emulate a clip that moves the target along X every frame.
+        let mut animated = REST;
+        for _ in 0..10 {
+            animated.x += 0.1;
+            app.world.get_mut::<Transform>(target).unwrap().translation = animated;
+            update(&mut app, FRAME);
+
+            let simulated = translation(&app, target);
+            assert!(simulated.x < animated.x);
+        }
+    }
+
+    #[test]
+    fn reset_snaps_back_to_animated_pose() {
+        let (mut app, target) = setup(Transform::IDENTITY, SecondaryMotion::new(100.0, 2.0, 1.0));
+        for _ in 0..5 {
+            update(&mut app, FRAME);
+        }
+        assert!(translation(&app, target).distance(REST) > 1e-3);
+
+        app.world
+            .get_mut::<SecondaryMotion>(target)
+            .unwrap()
+            .reset();
+        update(&mut app, FRAME);
+        // A single frame of gravity only moves the point a tiny amount.
+        assert!(translation(&app, target).distance(REST) < 1e-2);
+
+        for _ in 0..1200 {
+            update(&mut app, FRAME);
+        }
+        let settled = REST + SecondaryMotion::GRAVITY / 100.0;
+        assert!(translation(&app, target).distance(settled) < 1e-3);
+    }
+
+    #[test]
+    fn step_is_stable_with_heavy_damping() {
+        let mut motion = SecondaryMotion::new(10.0, 300.0, 0.0);
+        motion.step(Vec3::ZERO, 0.25);
+
+        let mut previous = Vec3::ZERO;
+        for _ in 0..50 {
+            let position = motion.step(Vec3::X, 0.25);
+            assert!(position.x >= previous.x && position.x <= 1.0);
+            previous = position;
+        }
+    }
+
+    #[test]
+    fn paused_time_holds_simulated_pose() {
+        let (mut app, target) = setup(Transform::IDENTITY, SecondaryMotion::new(100.0, 10.0, 1.0));
+        update(&mut app, 0.0);
+        assert!(translation(&app, target).distance(REST) < 1e-5);
+
+        // Emulate a clip that keeps the target at `REST`.
+        let animate = |app: &mut App, delta: f32| {
+            app.world.get_mut::<Transform>(target).unwrap().translation = REST;
+            update(app, delta);
+            translation(app, target)
+        };
+        for _ in 0..600 {
+            animate(&mut app, FRAME);
+        }
+        let settled = REST + SecondaryMotion::GRAVITY / 100.0;
+        assert!(animate(&mut app, FRAME).distance(settled) < 1e-3);
+
+        for _ in 0..10 {
+            assert!(animate(&mut app, 0.0).distance(settled) < 1e-3);
+        }
+        assert!(animate(&mut app, FRAME).distance(settled) < 1e-3);
+    }
+
+    #[test]
+    fn zero_scale_parents_restart_from_animated_pose() {
+        let (mut app, target) = setup(Transform::IDENTITY, SecondaryMotion::default());
+        for _ in 0..10 {
+            update(&mut app, FRAME);
+        }
+
+        let parent = app.world.get::<Parent>(target).unwrap().get();
+        let hidden = Transform::from_scale(Vec3::ZERO);
+        *app.world.get_mut::<GlobalTransform>(parent).unwrap() = hidden.into();
+        update(&mut app, FRAME);
+        assert_eq!(translation(&app, target), REST);
+
+        // Show the parent again somewhere else.
+        let shown = Transform::from_xyz(10.0, 0.0, 0.0);
+        *app.world.get_mut::<GlobalTransform>(parent).unwrap() = shown.into();
+        update(&mut app, FRAME);
+        assert!(translation(&app, target).distance(REST) < 1e-5);
+    }
+}
@@ -0,0 +1,190 @@
+//! Per-frame motion of animation targets, for motion blur and velocity
+//! buffers.
+
+use bevy_ecs::prelude::*;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_time::Time;
+use bevy_transform::prelude::{GlobalTransform, Transform};
+
+/// Records how an entity, typically an animated bone, moved between the last
+/// two frames.
+///
+/// This is meant as the input for per-bone velocity buffers used by motion
+/// blur and TAA. It's updated once per frame after transform propagation, so it
+/// reflects both the animated local [`Transform`] and the resulting
+/// [`GlobalTransform`].
+///
+/// The deltas are measured per frame and don't depend on [`Time`], so a target
+/// that is moved while the virtual clock is paused (for example through
+/// [`AnimationPlayer::seek_to`]) still reports its motion. Use
+/// [`Self::linear_velocity`] if you need units per second instead.
+///
+/// Nothing is reported on the first frame after the component is added.
+///
+/// [`AnimationPlayer::seek_to`]: crate::AnimationPlayer::seek_to
+#[derive(Clone, Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct AnimationVelocity {
+    previous_transform: Transform,
+    transform: Transform,
+    previous_global_transform: GlobalTransform,
+    global_transform: GlobalTransform,
+    /// The duration of the last frame, in seconds.
+    delta_seconds: f32,
+    /// Whether the transforms have been recorded at least once.
+    initialized: bool,
+}
+
+impl AnimationVelocity {
+    /// The local transform at the end of the previous frame.
+    pub fn previous_transform(&self) -> &Transform {
+        &self.previous_transform
+    }
+
+    /// The global transform at the end of the previous frame.
+    pub fn previous_global_transform(&self) -> &GlobalTransform {
+        &self.previous_global_transform
+    }
+
+    /// The change in local translation during the last frame.
+    pub fn translation_delta(&self) -> Vec3 {
+        self.transform.translation - self.previous_transform.translation
+    }
+
+    /// The change in local rotation during the last frame.
+    ///
+    /// Applying this to the previous rotation gives the current rotation.
+    pub fn rotation_delta(&self) -> Quat {
+        self.transform.rotation * self.previous_transform.rotation.inverse()
+    }
+
+    /// The change in local scale during the last frame.
+    pub fn scale_delta(&self) -> Vec3 {
+        self.transform.scale - self.previous_transform.scale
+    }
+
+    /// The change in world-space translation during the last frame.
+    pub fn global_translation_delta(&self) -> Vec3 {
+        self.global_transform.translation() - self.previous_global_transform.translation()
+    }
+
+    /// The change in world-space rotation during the last frame.
+    ///
+    /// Applying this to the previous world-space rotation gives the current
+    /// one.
+    pub fn global_rotation_delta(&self) -> Quat {
+        let (_, rotation, _) = self.global_transform.to_scale_rotation_translation();
+        let (_, previous_rotation, _) = self
+            .previous_global_transform
+            .to_scale_rotation_translation();
+        rotation * previous_rotation.inverse()
+    }
+
+    /// The world-space velocity, in units per second of [`Time`].
+    ///
+    /// This is [`Self::global_translation_delta`] divided by the frame time, and
+    /// is zero while the clock is paused.
+    pub fn linear_velocity(&self) -> Vec3 {
+        if self.delta_seconds <= 0.0 {
+            return Vec3::ZERO;
+        }
+        self.global_translation_delta() / self.delta_seconds
+    }
+
+    /// Forgets the recorded history, so that the next update reports no
+    /// motion. This is useful when a camera cut or respawn would otherwise
+    /// show up as one frame of very fast motion.
+    pub fn reset(&mut self) {
+        self.initialized = false;
+    }
+
+    fn record(&mut self, transform: Transform, global_transform: GlobalTransform, delta: f32) {
+        if self.initialized {
+            self.previous_transform = self.transform;
+            self.previous_global_transform = self.global_transform;
+        } else {
+            self.previous_transform = transform;
+            self.previous_global_transform = global_transform;
+            self.initialized = true;
+        }
+        self.transform = transform;
+        self.global_transform = global_transform;
+        self.delta_seconds = delta;
+    }
+}
+
+/// A system that updates [`AnimationVelocity`] from the animated and
+/// propagated transforms of each entity.
+pub fn record_animation_velocity(
+    time: Res<Time>,
+    mut targets: Query<(&mut AnimationVelocity, &Transform, &GlobalTransform)>,
+) {
+    let delta = time.delta_seconds();
+    targets
+        .par_iter_mut()
+        .for_each(|(mut velocity, transform, global_transform)| {
+            velocity.record(*transform, *global_transform, delta);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_app::{App, Update};
+    use bevy_core::TaskPoolPlugin;
+    use bevy_math::{Quat, Vec3};
+    use bevy_time::Time;
+    use bevy_transform::prelude::{GlobalTransform, Transform};
+
+    use super::{record_animation_velocity, AnimationVelocity};
+
+    #[test]
+    fn deltas_are_recorded_per_frame_regardless_of_time() {
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .init_resource::<Time>()
+            .add_systems(Update, record_animation_velocity);
+
+        let parent = GlobalTransform::from_xyz(0.0, 0.0, 10.0);
+        let transform = Transform::IDENTITY;
+        let entity = app
+            .world
+            .spawn((transform, parent * transform, AnimationVelocity::default()))
+            .id();
+
+        let advance = |app: &mut App, transform: Transform, delta: f32| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(delta));
+            let mut target = app.world.entity_mut(entity);
+            *target.get_mut::<Transform>().unwrap() = transform;
+            *target.get_mut::<GlobalTransform>().unwrap() = parent * transform;
+            app.update();
+            app.world.get::<AnimationVelocity>(entity).unwrap().clone()
+        };
+
+        let velocity = advance(&mut app, transform, 0.5);
+        assert_eq!(velocity.global_translation_delta(), Vec3::ZERO);
+
+        let moved = Transform::from_xyz(1.0, 0.0, 0.0).with_rotation(Quat::from_rotation_y(0.5));
+        let velocity = advance(&mut app, moved, 0.5);
+        assert_eq!(velocity.translation_delta(), Vec3::X);
+        assert_eq!(velocity.global_translation_delta(), Vec3::X);
+        assert_eq!(velocity.linear_velocity(), Vec3::new(2.0, 0.0, 0.0));
+        assert!(velocity
+            .global_rotation_delta()
+            .abs_diff_eq(Quat::from_rotation_y(0.5), 1e-5));
+        assert_eq!(velocity.previous_transform(), &transform);
+
+        // A paused clock doesn't hide a teleport from the per-frame deltas.
+        let teleported = Transform::from_xyz(5.0, 0.0, 0.0);
+        let velocity = advance(&mut app, teleported, 0.0);
+        assert_eq!(
+            velocity.global_translation_delta(),
+            Vec3::new(4.0, 0.0, 0.0)
+        );
+        assert_eq!(velocity.linear_velocity(), Vec3::ZERO);
+    }
+}